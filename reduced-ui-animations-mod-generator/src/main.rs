@@ -1,14 +1,19 @@
 #[cfg(test)]
 use pretty_assertions::assert_eq;
+use serde::Deserialize;
 use serde_json::json;
+use similar::{ChangeTag, TextDiff};
 use std::{
+    cell::RefCell,
     collections::BTreeMap,
+    io::Write,
     path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
 };
 use tracing::{debug, info, info_span};
 use tracing_subscriber::fmt::format::FmtSpan;
-use xml_dom::level2::{Document, Element, Name, Node, RefNode};
+use xml_dom::level2::{Document, Element, Name, Node, NodeType, RefNode};
 
 struct FileEntry {
     name: String,
@@ -59,6 +64,64 @@ impl ReadDirectory for FileSystem {
     }
 }
 
+/// A `ReadDirectory` backed by an in-memory `Directory` tree instead of the real file system, so
+/// `generate_mod` can be exercised against a synthetic game installation in tests.
+struct InMemoryDirectory {
+    root: Rc<Directory>,
+    path: Vec<String>,
+}
+
+impl InMemoryDirectory {
+    fn new(root: Directory) -> InMemoryDirectory {
+        InMemoryDirectory {
+            root: Rc::new(root),
+            path: Vec::new(),
+        }
+    }
+
+    fn current(&self) -> Option<&Directory> {
+        let mut current = self.root.as_ref();
+        for segment in &self.path {
+            match current.entries.get(segment) {
+                Some(DirectoryEntry::Subdirectory(subdirectory)) => {
+                    current = subdirectory.as_ref();
+                }
+                _ => return None,
+            }
+        }
+        Some(current)
+    }
+}
+
+impl ReadDirectory for InMemoryDirectory {
+    fn subdirectory(&self, name: &str) -> Box<dyn ReadDirectory> {
+        let mut path = self.path.clone();
+        path.push(name.to_string());
+        Box::new(InMemoryDirectory {
+            root: Rc::clone(&self.root),
+            path,
+        })
+    }
+
+    fn enumerate_files(&self) -> Box<dyn Iterator<Item = FileEntry>> {
+        let files: Vec<FileEntry> = match self.current() {
+            Some(directory) => directory
+                .entries
+                .iter()
+                .filter_map(|(name, entry)| match entry {
+                    DirectoryEntry::File(content) => Some(FileEntry {
+                        name: name.clone(),
+                        content: content.clone(),
+                    }),
+                    DirectoryEntry::Subdirectory(_) => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Box::new(files.into_iter())
+    }
+}
+
 trait WriteDirectory {
     fn subdirectory(&self, name: &str) -> Box<dyn WriteDirectory>;
     fn create_file(&self, name: &str, content: &[u8]);
@@ -83,11 +146,70 @@ impl WriteDirectory for FileSystem {
     }
 }
 
+/// A `WriteDirectory` that streams every file into a single `.zip` archive instead of writing a
+/// loose directory tree, so the generated mod can be distributed as one file.
+struct ZipDirectory {
+    writer: Rc<RefCell<zip::ZipWriter<std::fs::File>>>,
+    path_prefix: String,
+}
+
+impl ZipDirectory {
+    fn create(zip_file_path: &Path) -> ZipDirectory {
+        let file = std::fs::File::create(zip_file_path).expect("Tried to create a ZIP file");
+        ZipDirectory {
+            writer: Rc::new(RefCell::new(zip::ZipWriter::new(file))),
+            path_prefix: String::new(),
+        }
+    }
+
+    fn finish(self) {
+        self.writer
+            .borrow_mut()
+            .finish()
+            .expect("Tried to finish writing the ZIP file");
+    }
+
+    fn entry_name(&self, name: &str) -> String {
+        if self.path_prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", self.path_prefix, name)
+        }
+    }
+}
+
+impl WriteDirectory for ZipDirectory {
+    fn subdirectory(&self, name: &str) -> Box<dyn WriteDirectory> {
+        Box::new(ZipDirectory {
+            writer: Rc::clone(&self.writer),
+            path_prefix: self.entry_name(name),
+        })
+    }
+
+    fn create_file(&self, name: &str, content: &[u8]) {
+        let entry_name = self.entry_name(name);
+        debug!(
+            "Adding {} to the ZIP archive with {} bytes of content",
+            entry_name,
+            content.len()
+        );
+        let mut writer = self.writer.borrow_mut();
+        writer
+            .start_file(&entry_name, zip::write::FileOptions::default())
+            .expect("Tried to start a file in the ZIP archive");
+        writer
+            .write_all(content)
+            .expect("Tried to write file content into the ZIP archive");
+    }
+}
+
+#[derive(Debug, PartialEq)]
 enum DirectoryEntry {
     File(Vec<u8>),
     Subdirectory(Box<Directory>),
 }
 
+#[derive(Debug, PartialEq)]
 struct Directory {
     entries: BTreeMap<String, DirectoryEntry>,
 }
@@ -107,15 +229,72 @@ fn write_directory(data: &Directory, into: &dyn WriteDirectory) {
 
 const GENERATED_MOD_NAME: &str = "Reduced UI Animations";
 
-fn is_fade_brush_rectangle(node: &RefNode) -> bool {
-    let rectangle = Name::from_str("Rectangle").expect("Tried to parse an XML element name");
-    if node.node_name() != rectangle {
+const PATCH_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// A single rule for rewriting the attributes of an XAML element that matches by name and,
+/// optionally, by the value of one attribute.
+#[derive(Debug, Deserialize)]
+struct RewriteRule {
+    element_name: String,
+    match_attribute: Option<(String, String)>,
+    set_attributes: BTreeMap<String, String>,
+}
+
+/// The set of transformations `patch_xaml` applies to a XAML document. Loaded from a TOML file so
+/// that users can target other animated elements without recompiling.
+#[derive(Debug, Deserialize)]
+struct PatchConfig {
+    remove_elements: Vec<String>,
+    rewrite_elements: Vec<RewriteRule>,
+}
+
+/// The built-in rules, equivalent to what this tool used to hardcode: dropping the blur/swipe
+/// effects and turning the full-screen fade rectangle into a practically invisible one, just like
+/// "0xDB No UI Transitions 1.4" did.
+fn default_patch_config() -> PatchConfig {
+    PatchConfig {
+        remove_elements: vec![
+            "local:Age2BlurEffect".to_string(),
+            "local:Age2SwipeEffect".to_string(),
+        ],
+        rewrite_elements: vec![RewriteRule {
+            element_name: "Rectangle".to_string(),
+            match_attribute: Some(("x:Name".to_string(), "Fade".to_string())),
+            set_attributes: BTreeMap::from([
+                ("Canvas.Left".to_string(), "-1".to_string()),
+                ("Canvas.Top".to_string(), "-1".to_string()),
+                ("Fill".to_string(), "Green".to_string()),
+                ("Height".to_string(), "1".to_string()),
+                ("Width".to_string(), "1".to_string()),
+            ]),
+        }],
+    }
+}
+
+fn load_patch_config() -> PatchConfig {
+    match std::fs::read_to_string(PATCH_CONFIG_FILE_NAME) {
+        Ok(content) => toml::from_str(&content).expect("Tried to parse patch config file"),
+        Err(error) => {
+            info!(
+                "No patch config file found at {} ({}). Using the default patch rules.",
+                PATCH_CONFIG_FILE_NAME, error
+            );
+            default_patch_config()
+        }
+    }
+}
+
+fn element_matches_rewrite_rule(node: &RefNode, rule: &RewriteRule) -> bool {
+    let element_name =
+        Name::from_str(&rule.element_name).expect("Tried to parse an XML element name");
+    if node.node_name() != element_name {
         return false;
     }
-    let maybe_name = node.get_attribute("x:Name");
-    match maybe_name {
-        Some(name) => name == "Fade",
-        None => false,
+    match &rule.match_attribute {
+        Some((attribute_name, attribute_value)) => {
+            node.get_attribute(attribute_name).as_deref() == Some(attribute_value.as_str())
+        }
+        None => true,
     }
 }
 
@@ -124,15 +303,18 @@ enum PatchStatus {
     Changed,
 }
 
-fn patch_xaml_recursively(node: &mut RefNode) -> PatchStatus {
-    let blur_effect =
-        Name::from_str("local:Age2BlurEffect").expect("Tried to parse an XML element name");
-    let swipe_effect =
-        Name::from_str("local:Age2SwipeEffect").expect("Tried to parse an XML element name");
+fn patch_xaml_recursively(node: &mut RefNode, config: &PatchConfig) -> PatchStatus {
+    let remove_elements: Vec<Name> = config
+        .remove_elements
+        .iter()
+        .map(|element_name| {
+            Name::from_str(element_name).expect("Tried to parse an XML element name")
+        })
+        .collect();
     let mut result: PatchStatus = PatchStatus::Unchanged;
     for mut child in node.child_nodes() {
         let name = child.node_name();
-        if (name == blur_effect) || (name == swipe_effect) {
+        if remove_elements.contains(&name) {
             info!("Removing child node: {}", child.node_name());
             node.replace_child(
                 node.owner_document()
@@ -146,17 +328,20 @@ fn patch_xaml_recursively(node: &mut RefNode) -> PatchStatus {
             .expect("Tried to replace an element with a comment");
             result = PatchStatus::Changed;
             continue;
-        } else if is_fade_brush_rectangle(&child) {
-            info!("Rewriting fade brush element");
-            // just do it like "0xDB No UI Transitions 1.4"
-            child.set_attribute("Canvas.Left", "-1").unwrap();
-            child.set_attribute("Canvas.Top", "-1").unwrap();
-            child.set_attribute("Fill", "Green").unwrap();
-            child.set_attribute("Height", "1").unwrap();
-            child.set_attribute("Width", "1").unwrap();
+        } else if let Some(rule) = config
+            .rewrite_elements
+            .iter()
+            .find(|rule| element_matches_rewrite_rule(&child, rule))
+        {
+            info!("Rewriting element matched by rule: {}", rule.element_name);
+            for (attribute_name, attribute_value) in &rule.set_attributes {
+                child
+                    .set_attribute(attribute_name, attribute_value)
+                    .unwrap();
+            }
             result = PatchStatus::Changed;
         }
-        match patch_xaml_recursively(&mut child) {
+        match patch_xaml_recursively(&mut child, config) {
             PatchStatus::Unchanged => {}
             PatchStatus::Changed => result = PatchStatus::Changed,
         }
@@ -164,14 +349,98 @@ fn patch_xaml_recursively(node: &mut RefNode) -> PatchStatus {
     result
 }
 
+fn is_namespace_declaration(name: &Name) -> bool {
+    let name_string = name.to_string();
+    name_string == "xmlns" || name_string.starts_with("xmlns:")
+}
+
+fn escape_attribute_value(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn write_element(node: &RefNode, output: &mut String) {
+    let tag_name = node.node_name().to_string();
+    output.push('<');
+    output.push_str(&tag_name);
+
+    let attributes = node.attributes();
+    let mut attribute_names: Vec<&Name> = attributes.keys().collect();
+    attribute_names.sort_by(|left, right| {
+        match (
+            is_namespace_declaration(left),
+            is_namespace_declaration(right),
+        ) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => left.to_string().cmp(&right.to_string()),
+        }
+    });
+    for attribute_name in attribute_names {
+        output.push(' ');
+        output.push_str(&attribute_name.to_string());
+        output.push_str("=\"");
+        output.push_str(&escape_attribute_value(&attributes[attribute_name]));
+        output.push('"');
+    }
+
+    let children = node.child_nodes();
+    if children.is_empty() {
+        output.push_str(" />");
+        return;
+    }
+    output.push('>');
+    for child in &children {
+        write_node(child, output);
+    }
+    output.push_str("</");
+    output.push_str(&tag_name);
+    output.push('>');
+}
+
+fn write_node(node: &RefNode, output: &mut String) {
+    match node.node_type() {
+        NodeType::Element => write_element(node, output),
+        NodeType::Text => output.push_str(&escape_text(&node.node_value().unwrap_or_default())),
+        NodeType::Comment => {
+            output.push_str("<!--");
+            output.push_str(&node.node_value().unwrap_or_default());
+            output.push_str("-->");
+        }
+        NodeType::CData => {
+            output.push_str("<![CDATA[");
+            output.push_str(&node.node_value().unwrap_or_default());
+            output.push_str("]]>");
+        }
+        _ => {
+            for child in &node.child_nodes() {
+                write_node(child, output);
+            }
+        }
+    }
+}
+
+/// Serializes the DOM tree to XML the same way every time, unlike `RefNode::to_string`, which
+/// iterates attributes in whatever order its backing `HashMap` happens to give them.
 fn xml_to_string(root: &RefNode) -> String {
-    // TODO: find a deterministic solution. The order of attributes is random because they use HashMap to store them and don't normalize for formatting. Seriously, wtf?
-    root.to_string()
+    let mut output = String::new();
+    write_node(root, &mut output);
+    output
 }
 
-fn patch_xaml(original_content: &str) -> Option<String> {
+fn patch_xaml(original_content: &str, config: &PatchConfig) -> Option<String> {
     let mut root = xml_dom::parser::read_xml(original_content).expect("Tried to parse XML");
-    match patch_xaml_recursively(&mut root) {
+    match patch_xaml_recursively(&mut root, config) {
         PatchStatus::Unchanged => None,
         PatchStatus::Changed => Some(xml_to_string(&root)),
     }
@@ -179,14 +448,17 @@ fn patch_xaml(original_content: &str) -> Option<String> {
 
 #[test]
 fn test_patch_xaml_tiny() {
-    assert_eq!(None, patch_xaml(r#"<Test xmlns="test"></Test>"#));
+    assert_eq!(
+        None,
+        patch_xaml(r#"<Test xmlns="test"></Test>"#, &default_patch_config())
+    );
 }
 
 #[test]
 fn test_patch_xaml_swipe_effect() {
     assert_eq!(
         Some( "<Test xmlns=\"test\" xmlns:local=\"bla\"><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Test>".to_string()),
-        patch_xaml(r#"<Test xmlns="test" xmlns:local="bla"><local:Age2SwipeEffect/></Test>"#)
+        patch_xaml(r#"<Test xmlns="test" xmlns:local="bla"><local:Age2SwipeEffect/></Test>"#, &default_patch_config())
     );
 }
 
@@ -195,7 +467,8 @@ fn test_patch_xaml_blur_effect() {
     assert_eq!(
         Some("<Test xmlns=\"test\" xmlns:local=\"bla\"><Canvas.Effect>\\n<!--The mod Reduced UI Animations replaced an element here: local:Age2BlurEffect--></Canvas.Effect></Test>".to_string()),
         patch_xaml(
-            r#"<Test xmlns="test" xmlns:local="bla"><Canvas.Effect>\n<local:Age2BlurEffect /></Canvas.Effect></Test>"#
+            r#"<Test xmlns="test" xmlns:local="bla"><Canvas.Effect>\n<local:Age2BlurEffect /></Canvas.Effect></Test>"#,
+            &default_patch_config()
         )
     );
 }
@@ -204,18 +477,19 @@ fn test_patch_xaml_blur_effect() {
 fn test_patch_xaml_fade_brush() {
     assert_eq!(
         Some(
-            "<Test xmlns=\"test\"><!--a fade over the screen, but under the modals--><Rectangle Width=\"1\" Fill=\"Green\" Canvas.Left=\"-1\" Canvas.Top=\"-1\" Height=\"1\" x:Name=\"Fade\" Visibility=\"Hidden\"></Rectangle></Test>"
+            "<Test xmlns=\"test\"><!--a fade over the screen, but under the modals--><Rectangle Canvas.Left=\"-1\" Canvas.Top=\"-1\" Fill=\"Green\" Height=\"1\" Visibility=\"Hidden\" Width=\"1\" x:Name=\"Fade\" /></Test>"
                 .to_string()
         ),
         patch_xaml(
             r#"<Test xmlns="test"><!--a fade over the screen, but under the modals-->
-        <Rectangle 
+        <Rectangle
            x:Name="Fade"
-           Fill="{Binding ElementName=window, Path=FadeBrush}" 
+           Fill="{Binding ElementName=window, Path=FadeBrush}"
            Visibility="Hidden"
            Height="{Binding ElementName=window, Path=ActualHeight}"
-           Width="{Binding ElementName=window, Path=ActualWidth}" 
-           /></Test>"#
+           Width="{Binding ElementName=window, Path=ActualWidth}"
+           /></Test>"#,
+            &default_patch_config()
         )
     );
 }
@@ -225,7 +499,8 @@ fn test_patch_xaml_two_different_effects() {
     assert_eq!(
        Some(  "<Test xmlns=\"test\" xmlns:local=\"bla\"><Canvas.Effect>\\n<!--The mod Reduced UI Animations replaced an element here: local:Age2BlurEffect--></Canvas.Effect><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Test>".to_string()),
         patch_xaml(
-            r#"<Test xmlns="test" xmlns:local="bla"><Canvas.Effect>\n<local:Age2BlurEffect /></Canvas.Effect><local:Age2SwipeEffect/></Test>"#
+            r#"<Test xmlns="test" xmlns:local="bla"><Canvas.Effect>\n<local:Age2BlurEffect /></Canvas.Effect><local:Age2SwipeEffect/></Test>"#,
+            &default_patch_config()
         )
     );
 }
@@ -233,9 +508,10 @@ fn test_patch_xaml_two_different_effects() {
 #[test]
 fn test_patch_xaml_same_effect_twice() {
     assert_eq!(
-       Some(  "<Test xmlns:local=\"bla\" xmlns=\"test\"><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Test>".to_string()),
+       Some(  "<Test xmlns=\"test\" xmlns:local=\"bla\"><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Test>".to_string()),
         patch_xaml(
-            r#"<Test xmlns="test" xmlns:local="bla"><local:Age2SwipeEffect/><local:Age2SwipeEffect/></Test>"#
+            r#"<Test xmlns="test" xmlns:local="bla"><local:Age2SwipeEffect/><local:Age2SwipeEffect/></Test>"#,
+            &default_patch_config()
         )
     );
 }
@@ -243,7 +519,7 @@ fn test_patch_xaml_same_effect_twice() {
 #[test]
 fn test_patch_xaml_realistic() {
     assert_eq!(
-       Some(  "<local:Age2ScreenSimpleMainMenu xmlns:local=\"clr-namespace:aoe2wpfg\" xmlns:x=\"http://schemas.microsoft.com/winfx/2006/xaml\" xmlns:d=\"http://schemas.microsoft.com/expression/blend/2008\" xmlns=\"http://schemas.microsoft.com/winfx/2006/xaml/presentation\" xmlns:mc=\"http://schemas.openxmlformats.org/markup-compatibility/2006\" d:DesignWidth=\"3840\" mc:Ignorable=\"d\" x:Name=\"Page\" d:DesignHeight=\"2160\"><Canvas Height=\"2160\" Background=\"Transparent\" Width=\"3840\"><Canvas.Effect><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Canvas.Effect></Canvas><Canvas Width=\"1000\" Background=\"Transparent\" Canvas.Left=\"235\" Height=\"2160\"><Canvas.Effect><!--The mod Reduced UI Animations replaced an element here: local:Age2BlurEffect--></Canvas.Effect></Canvas></local:Age2ScreenSimpleMainMenu>".to_string()),
+       Some(  "<local:Age2ScreenSimpleMainMenu xmlns=\"http://schemas.microsoft.com/winfx/2006/xaml/presentation\" xmlns:d=\"http://schemas.microsoft.com/expression/blend/2008\" xmlns:local=\"clr-namespace:aoe2wpfg\" xmlns:mc=\"http://schemas.openxmlformats.org/markup-compatibility/2006\" xmlns:x=\"http://schemas.microsoft.com/winfx/2006/xaml\" d:DesignHeight=\"2160\" d:DesignWidth=\"3840\" mc:Ignorable=\"d\" x:Name=\"Page\"><Canvas Background=\"Transparent\" Height=\"2160\" Width=\"3840\"><Canvas.Effect><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Canvas.Effect></Canvas><Canvas Background=\"Transparent\" Canvas.Left=\"235\" Height=\"2160\" Width=\"1000\"><Canvas.Effect><!--The mod Reduced UI Animations replaced an element here: local:Age2BlurEffect--></Canvas.Effect></Canvas></local:Age2ScreenSimpleMainMenu>".to_string()),
         patch_xaml(
             r#"<local:Age2ScreenSimpleMainMenu x:Name="Page" d:DesignHeight="2160" d:DesignWidth="3840" mc:Ignorable="d" xmlns="http://schemas.microsoft.com/winfx/2006/xaml/presentation" xmlns:d="http://schemas.microsoft.com/expression/blend/2008" xmlns:local="clr-namespace:aoe2wpfg" xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" xmlns:x="http://schemas.microsoft.com/winfx/2006/xaml">
     <Canvas Width="3840" Height="2160" Background="Transparent">
@@ -278,26 +554,45 @@ fn test_patch_xaml_realistic() {
         </Canvas.Effect>
     </Canvas>
 </local:Age2ScreenSimpleMainMenu>
-"#
+"#,
+            &default_patch_config()
         )
     );
 }
 
-fn modify_xaml_file(original_content: &[u8]) -> Option<Vec<u8>> {
+fn modify_xaml_file(original_content: &[u8], config: &PatchConfig) -> Option<Vec<u8>> {
     let original_content_string = encoding_rs::UTF_8
         .decode_with_bom_removal(original_content)
         .0;
-    let modified_content = patch_xaml(original_content_string.as_ref());
+    let modified_content = patch_xaml(original_content_string.as_ref(), config);
     modified_content.map(|value| value.into())
 }
 
-fn modify_xaml_files<'t>(directory: &'t dyn ReadDirectory) -> BTreeMap<String, DirectoryEntry> {
+/// A single XAML file that a dry run would have rewritten, kept alongside its original content so
+/// a diff can be shown without touching the destination mod directory.
+struct XamlChange {
+    relative_path: String,
+    original_content: Vec<u8>,
+    modified_content: Vec<u8>,
+}
+
+fn modify_xaml_files<'t>(
+    directory: &'t dyn ReadDirectory,
+    config: &PatchConfig,
+    directory_path: &str,
+    changes: &mut Vec<XamlChange>,
+) -> BTreeMap<String, DirectoryEntry> {
     let mut entries = BTreeMap::new();
     for file_entry in directory.enumerate_files() {
-        let maybe_modified = modify_xaml_file(&file_entry.content);
+        let maybe_modified = modify_xaml_file(&file_entry.content, config);
         match maybe_modified {
             Some(modified) => {
                 info!("XAML file will be replaced: {}", &file_entry.name);
+                changes.push(XamlChange {
+                    relative_path: format!("{}/{}", directory_path, &file_entry.name),
+                    original_content: file_entry.content.clone(),
+                    modified_content: modified.clone(),
+                });
                 entries.insert(file_entry.name, DirectoryEntry::File(modified));
             }
             None => info!("XAML file needs no changes: {}", &file_entry.name),
@@ -306,12 +601,22 @@ fn modify_xaml_files<'t>(directory: &'t dyn ReadDirectory) -> BTreeMap<String, D
     entries
 }
 
-fn modify_wpfg<'t>(wpfg_installation: &'t (dyn ReadDirectory + 't)) -> Directory {
-    let mut entries = modify_xaml_files(wpfg_installation);
+fn modify_wpfg<'t>(
+    wpfg_installation: &'t (dyn ReadDirectory + 't),
+    config: &PatchConfig,
+    changes: &mut Vec<XamlChange>,
+) -> Directory {
+    let mut entries = modify_xaml_files(wpfg_installation, config, "wpfg", changes);
     for subdirectory in ["dialog", "panel", "screen", "tab"] {
         let _span = info_span!("Modding", subdirectory);
         let subdirectory_reader = wpfg_installation.subdirectory(subdirectory);
-        let modified_files = modify_xaml_files(subdirectory_reader.as_ref());
+        let subdirectory_path = format!("wpfg/{}", subdirectory);
+        let modified_files = modify_xaml_files(
+            subdirectory_reader.as_ref(),
+            config,
+            &subdirectory_path,
+            changes,
+        );
         entries.insert(
             subdirectory.to_string(),
             DirectoryEntry::Subdirectory(Box::new(Directory {
@@ -340,7 +645,11 @@ fn test_create_info_json() {
     );
 }
 
-fn generate_mod(game_installation: &dyn ReadDirectory) -> Directory {
+fn generate_mod(
+    game_installation: &dyn ReadDirectory,
+    config: &PatchConfig,
+    changes: &mut Vec<XamlChange>,
+) -> Directory {
     let mut entries = BTreeMap::new();
 
     {
@@ -364,7 +673,7 @@ fn generate_mod(game_installation: &dyn ReadDirectory) -> Directory {
     let wpfg = common.subdirectory(wpfg_directory_name);
 
     let _span = info_span!("Modding wpfg");
-    let modified = modify_wpfg(wpfg.as_ref());
+    let modified = modify_wpfg(wpfg.as_ref(), config, changes);
     entries.insert(
         resources_directory_name.to_string(),
         DirectoryEntry::Subdirectory(Box::new(Directory {
@@ -383,6 +692,104 @@ fn generate_mod(game_installation: &dyn ReadDirectory) -> Directory {
     Directory { entries: entries }
 }
 
+#[test]
+fn test_generate_mod_with_in_memory_read_directory() {
+    let empty_directory = || {
+        DirectoryEntry::Subdirectory(Box::new(Directory {
+            entries: BTreeMap::new(),
+        }))
+    };
+    let wpfg = Directory {
+        entries: BTreeMap::from([
+            (
+                "main.xaml".to_string(),
+                DirectoryEntry::File(
+                    br#"<Test xmlns="test" xmlns:local="bla"><local:Age2SwipeEffect/></Test>"#
+                        .to_vec(),
+                ),
+            ),
+            ("dialog".to_string(), empty_directory()),
+            ("panel".to_string(), empty_directory()),
+            ("screen".to_string(), empty_directory()),
+            ("tab".to_string(), empty_directory()),
+        ]),
+    };
+    let common = Directory {
+        entries: BTreeMap::from([(
+            "wpfg".to_string(),
+            DirectoryEntry::Subdirectory(Box::new(wpfg)),
+        )]),
+    };
+    let resources = Directory {
+        entries: BTreeMap::from([(
+            "_common".to_string(),
+            DirectoryEntry::Subdirectory(Box::new(common)),
+        )]),
+    };
+    let game_installation = Directory {
+        entries: BTreeMap::from([(
+            "resources".to_string(),
+            DirectoryEntry::Subdirectory(Box::new(resources)),
+        )]),
+    };
+
+    let mut changes = Vec::new();
+    let generated_mod = generate_mod(
+        &InMemoryDirectory::new(game_installation),
+        &default_patch_config(),
+        &mut changes,
+    );
+
+    match generated_mod.entries.get("info.json") {
+        Some(DirectoryEntry::File(_)) => {}
+        other => panic!("Expected info.json to be a file, got {:?}", other),
+    }
+
+    let wpfg_entries = match generated_mod.entries.get("resources") {
+        Some(DirectoryEntry::Subdirectory(resources)) => match resources.entries.get("_common") {
+            Some(DirectoryEntry::Subdirectory(common)) => match common.entries.get("wpfg") {
+                Some(DirectoryEntry::Subdirectory(wpfg)) => &wpfg.entries,
+                other => panic!(
+                    "Expected resources/_common/wpfg to be a directory, got {:?}",
+                    other
+                ),
+            },
+            other => panic!(
+                "Expected resources/_common to be a directory, got {:?}",
+                other
+            ),
+        },
+        other => panic!("Expected resources to be a directory, got {:?}", other),
+    };
+    assert_eq!(
+        Some(&DirectoryEntry::File(
+            "<Test xmlns=\"test\" xmlns:local=\"bla\"><!--The mod Reduced UI Animations replaced an element here: local:Age2SwipeEffect--></Test>"
+                .as_bytes()
+                .to_vec()
+        )),
+        wpfg_entries.get("main.xaml")
+    );
+}
+
+fn print_dry_run_diff(change: &XamlChange) {
+    let original_text = String::from_utf8_lossy(&change.original_content);
+    let modified_text = String::from_utf8_lossy(&change.modified_content);
+    println!("--- {}", change.relative_path);
+    println!("+++ {}", change.relative_path);
+    let diff = TextDiff::from_lines(original_text.as_ref(), modified_text.as_ref());
+    for hunk in diff.unified_diff().iter_hunks() {
+        println!("{}", hunk.header());
+        for change in hunk.iter_changes() {
+            let sign = match change.tag() {
+                ChangeTag::Delete => "-",
+                ChangeTag::Insert => "+",
+                ChangeTag::Equal => " ",
+            };
+            print!("{}{}", sign, change);
+        }
+    }
+}
+
 fn main() {
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::TRACE)
@@ -390,6 +797,9 @@ fn main() {
         .with_target(false)
         .init();
     let _span = info_span!("Mod generator");
+    let dry_run = std::env::args().any(|argument| argument == "--dry-run");
+    let zip_output = std::env::args().any(|argument| argument == "--zip");
+    let patch_config = load_patch_config();
     let aoe2de_installation = Path::new("C:/Program Files (x86)/Steam/steamapps/common/AoE2DE");
     let user_name = whoami::username();
     let home = Path::new("C:/Users").join(user_name);
@@ -401,9 +811,39 @@ fn main() {
     let destination_directory = local_mods.join(GENERATED_MOD_NAME);
     info!("Aoe2 DE installation: {}", aoe2de_installation.display());
     info!("Generating mod into {}", destination_directory.display());
-    let generated_mod = generate_mod(&FileSystem {
-        root: aoe2de_installation.into(),
-    });
+    let mut xaml_changes = Vec::new();
+    let generated_mod = generate_mod(
+        &FileSystem {
+            root: aoe2de_installation.into(),
+        },
+        &patch_config,
+        &mut xaml_changes,
+    );
+
+    if dry_run {
+        info!(
+            "Dry run: {} XAML file(s) would change, nothing will be written to {}",
+            xaml_changes.len(),
+            destination_directory.display()
+        );
+        for change in &xaml_changes {
+            print_dry_run_diff(change);
+        }
+        return;
+    }
+
+    if zip_output {
+        let zip_file_path = destination_directory.with_extension("zip");
+        info!(
+            "Writing the mod into a ZIP archive: {}",
+            zip_file_path.display()
+        );
+        let zip_directory = ZipDirectory::create(&zip_file_path);
+        write_directory(&generated_mod, &zip_directory);
+        zip_directory.finish();
+        return;
+    }
+
     match std::fs::metadata(&destination_directory) {
         Ok(exists) => {
             assert!(exists.is_dir());